@@ -6,7 +6,7 @@
 use core::fmt::*;
 use std::mem::{needs_drop, take, transmute, MaybeUninit};
 use std::ops::{Index, IndexMut, Range};
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use pi_arr::{Arr, Iter};
 use pi_share::ShareUsize;
@@ -89,6 +89,45 @@ impl<T: Default> AppendVec<T> {
         *self.arr.load_alloc(index) = value;
         index
     }
+    /// 一次性保留`n`个连续下标，返回起始下标和这些槽位的写入句柄，避免批量插入时逐个`fetch_add`
+    #[inline(always)]
+    pub fn reserve_block(&self, n: usize) -> (usize, Block<'_, T>) {
+        let start = self.alloc_index(n);
+        (
+            start,
+            Block {
+                arr: &self.arr,
+                cur: start,
+                end: start + n,
+            },
+        )
+    }
+    /// 批量插入，等价于对`iter`中的每个元素调用`insert`，但只做一次`fetch_add`
+    #[inline(always)]
+    pub fn extend<I: IntoIterator<Item = T>>(&self, iter: I)
+    where
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        let n = iter.len();
+        if n == 0 {
+            return;
+        }
+        let (_, block) = self.reserve_block(n);
+        let mut written = 0;
+        for (slot, value) in block.zip(iter) {
+            *slot = value;
+            written += 1;
+        }
+        // ExactSizeIterator::len()是安全trait上的约定而非unsafe契约，不可信的实现会触发这里的
+        // panic；但reserve_block已经用fetch_add把len永久前移到start+n，这个panic只是把"静默读到
+        // 默认值"变成"立刻崩溃"，并不能阻止0..len()里混入未写入的槽位——调用方若用thread::spawn
+        // +join或catch_unwind挺过这次panic，拿到的AppendVec仍会有一段默认值槽位留在有效区间内
+        assert_eq!(
+            written, n,
+            "extend: iterator yielded fewer elements than its reported len"
+        );
+    }
     #[inline(always)]
     pub fn iter(&self) -> Iter<'_, T> {
         self.slice_raw(0..self.len())
@@ -105,6 +144,14 @@ impl<T: Default> AppendVec<T> {
     pub fn slice_raw(&self, range: Range<usize>) -> Iter<'_, T> {
         self.arr.slice(range)
     }
+    /// 将有效区间收集为一个`std::Vec`
+    #[inline(always)]
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.iter().cloned().collect()
+    }
     #[inline(always)]
     pub unsafe fn set_len(&self, len: usize) {
         self.len.store(len, Ordering::Relaxed);
@@ -126,6 +173,33 @@ impl<T: Default> AppendVec<T> {
         }
         self.arr.settle(len, additional, 1);
     }
+    /// 保留满足条件的元素，其余丢弃，幸存元素前移补齐空隙，然后整理内存使其连续
+    #[inline(always)]
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, additional: usize, mut f: F) {
+        let len = *self.len.get_mut();
+        if len == 0 {
+            return;
+        }
+        let mut w = 0;
+        for r in 0..len {
+            let keep = f(unsafe { self.arr.get_unchecked(r) });
+            if keep {
+                if w != r {
+                    // 交换而不是覆盖，被丢弃的元素随之移到尾部
+                    let dst = unsafe { self.arr.get_unchecked_mut(w) } as *mut T;
+                    let src = unsafe { self.arr.get_unchecked_mut(r) } as *mut T;
+                    unsafe { std::ptr::swap(dst, src) };
+                }
+                w += 1;
+            }
+        }
+        // `[w, len)`只剩被丢弃的元素，逐个析构
+        for i in w..len {
+            take(unsafe { self.arr.get_unchecked_mut(i) });
+        }
+        *self.len.get_mut() = w;
+        self.settle(additional);
+    }
     /// 清理，并释放arr的内存
     #[inline(always)]
     pub fn clear(&mut self, additional: usize) {
@@ -135,6 +209,29 @@ impl<T: Default> AppendVec<T> {
         }
         self.arr.clear(len, additional, 1);
     }
+    /// 弹出末尾元素，要求独占引用，因此不需要原子操作
+    #[inline(always)]
+    pub fn pop(&mut self) -> Option<T> {
+        let len = self.len.get_mut();
+        if *len == 0 {
+            return None;
+        }
+        *len -= 1;
+        let index = *len;
+        Some(take(unsafe { self.arr.get_unchecked_mut(index) }))
+    }
+    /// 截断到指定长度，丢弃`len..`区间的元素，要求独占引用，因此不需要原子操作
+    #[inline(always)]
+    pub fn truncate(&mut self, len: usize) {
+        let cur = *self.len.get_mut();
+        if len >= cur {
+            return;
+        }
+        for i in len..cur {
+            take(unsafe { self.arr.get_unchecked_mut(i) });
+        }
+        *self.len.get_mut() = len;
+    }
 }
 impl<T: Default> Index<usize> for AppendVec<T> {
     type Output = T;
@@ -160,6 +257,27 @@ impl<T: Default> Default for AppendVec<T> {
         Self::with_capacity(0)
     }
 }
+impl<T: Default + Clone> Clone for AppendVec<T> {
+    fn clone(&self) -> Self {
+        let len = self.len();
+        let vec = Self::with_capacity(len);
+        for i in 0..len {
+            vec.insert(self[i].clone());
+        }
+        vec
+    }
+}
+impl<T: Default> FromIterator<T> for AppendVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        let vec = Self::with_capacity(lower);
+        for value in iter {
+            vec.insert(value);
+        }
+        vec
+    }
+}
 
 pub struct SafeVec<T> {
     vec: AppendVec<Element<T>>,
@@ -178,7 +296,12 @@ impl<T> SafeVec<T> {
     pub fn capacity(&self) -> usize {
         self.vec.arr.capacity(self.len())
     }
-    /// 长度
+    /// 长度，只会暴露ready位已置位的连续前缀；通过共享引用插入时单调递增，
+    /// 只有要求`&mut self`的`pop`、`truncate`、`retain`、`clear`会让它变小
+    ///
+    /// 不变式：任何下标 >= `len()` 的槽位都不能有`ready == true`。任何会缩短长度的操作
+    /// （`pop`、`retain`等）在回退这个计数的同时，必须把被放弃槽位的ready位重置为`false`，
+    /// 并同步回退内部分配计数`vec.len`，否则`advance_committed_len`会把已经失效的槽位重新暴露出来
     #[inline(always)]
     pub fn len(&self) -> usize {
         self.len.load(Ordering::Acquire)
@@ -189,11 +312,11 @@ impl<T> SafeVec<T> {
         if index >= len {
             return None;
         }
-        self.vec.get(index).map(|r| unsafe { &*r.0.as_ptr() })
+        self.vec.get(index).map(|r| unsafe { &*r.value.as_ptr() })
     }
     #[inline(always)]
     pub unsafe fn get_unchecked(&self, index: usize) -> &T {
-        &*self.vec.get_unchecked(index).0.as_ptr()
+        &*self.vec.get_unchecked(index).value.as_ptr()
     }
     #[inline(always)]
     pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
@@ -203,42 +326,70 @@ impl<T> SafeVec<T> {
         }
         self.vec
             .get_mut(index)
-            .map(|r| unsafe { &mut *r.0.as_mut_ptr() })
+            .map(|r| unsafe { &mut *r.value.as_mut_ptr() })
     }
     #[inline(always)]
     pub unsafe fn get_unchecked_mut(&mut self, index: usize) -> &mut T {
-        &mut *self.vec.get_unchecked_mut(index).0.as_mut_ptr()
+        &mut *self.vec.get_unchecked_mut(index).value.as_mut_ptr()
     }
     #[inline(always)]
     pub fn load(&self, index: usize) -> Option<&mut T> {
         self.vec
             .load(index)
-            .map(|r| unsafe { &mut *r.0.as_mut_ptr() })
+            .map(|r| unsafe { &mut *r.value.as_mut_ptr() })
     }
     #[inline(always)]
     pub unsafe fn load_unchecked(&self, index: usize) -> &mut T {
-        &mut *self.vec.load_unchecked(index).0.as_mut_ptr()
+        &mut *self.vec.load_unchecked(index).value.as_mut_ptr()
     }
 
     #[inline(always)]
     pub fn insert(&self, value: T) -> usize {
         let (r, index) = self.vec.alloc();
-        *r = Element(MaybeUninit::new(value));
-        while self
-            .len
-            .compare_exchange(index, index + 1, Ordering::Release, Ordering::Relaxed)
-            .is_err()
-        {
-            std::hint::spin_loop();
-        }
+        r.value = MaybeUninit::new(value);
+        r.ready.store(true, Ordering::Release);
+        advance_committed_len(&self.len, &self.vec);
         index
     }
+    /// 批量插入，一次性保留整个区块再写入，逐个置位ready后只尝试推进一次`len`
+    #[inline(always)]
+    pub fn extend<I: IntoIterator<Item = T>>(&self, iter: I)
+    where
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        let n = iter.len();
+        if n == 0 {
+            return;
+        }
+        let (start, block) = self.vec.reserve_block(n);
+        let mut written = 0;
+        for (slot, value) in block.zip(iter) {
+            slot.value = MaybeUninit::new(value);
+            written += 1;
+        }
+        // ExactSizeIterator::len()是安全trait上的约定而非unsafe契约，不可信的实现一旦少写，
+        // [start+written, start+n)这段槽位就永远不会被置位ready，advance_committed_len会在
+        // 这里永久卡住、隐藏之后任何线程插入的新元素；必须当场panic而不是debug-only断言
+        assert_eq!(
+            written, n,
+            "extend: iterator yielded fewer elements than its reported len"
+        );
+        // 只为实际写入的槽位置位ready，一个报告过长的迭代器不会发布未初始化的槽位
+        for i in start..start + written {
+            unsafe { self.vec.get_unchecked(i) }
+                .ready
+                .store(true, Ordering::Release);
+        }
+        advance_committed_len(&self.len, &self.vec);
+    }
     #[inline(always)]
     pub fn alloc_entry<'a>(&'a self) -> Entry<'a, T> {
         let (value, index) = self.vec.alloc();
         Entry {
             index,
             len: &self.len,
+            vec: &self.vec,
             value,
         }
     }
@@ -250,6 +401,16 @@ impl<T> SafeVec<T> {
     pub fn slice(&self, range: Range<usize>) -> SafeVecIter<'_, T> {
         SafeVecIter(self.vec.slice(range))
     }
+    /// 将有效区间收集为一个`std::Vec`
+    #[inline(always)]
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        (0..self.len())
+            .map(|i| self.get(i).expect("live element").clone())
+            .collect()
+    }
     pub fn vec_capacity(&self) -> usize {
         self.vec.vec_capacity()
     }
@@ -257,6 +418,40 @@ impl<T> SafeVec<T> {
     pub fn settle(&mut self, additional: usize) {
         self.vec.settle(additional);
     }
+    /// 保留满足条件的元素，其余丢弃，幸存元素前移补齐空隙，然后整理内存使其连续
+    #[inline(always)]
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, additional: usize, mut f: F) {
+        let len = take(self.len.get_mut());
+        if len == 0 {
+            return;
+        }
+        let mut w = 0;
+        for r in 0..len {
+            let keep = {
+                let element = unsafe { self.vec.get_unchecked(r) };
+                f(unsafe { &*element.value.as_ptr() })
+            };
+            if keep {
+                if w != r {
+                    let value = unsafe { self.vec.get_unchecked_mut(r).value.assume_init_read() };
+                    unsafe { self.vec.get_unchecked_mut(w) }.value = MaybeUninit::new(value);
+                }
+                w += 1;
+            } else if needs_drop::<T>() {
+                unsafe { self.vec.get_unchecked_mut(r).value.assume_init_drop() };
+            }
+        }
+        // 被放弃的`[w, len)`区间，无论是被丢弃的元素还是搬移后留下的重复字节，都不再有效，
+        // 必须清空ready位并回退内部分配计数，否则advance_committed_len会把它们重新暴露出来
+        for i in w..len {
+            unsafe { self.vec.get_unchecked_mut(i) }
+                .ready
+                .store(false, Ordering::Relaxed);
+        }
+        *self.len.get_mut() = w;
+        *self.vec.len.get_mut() = w;
+        self.vec.settle(additional);
+    }
 
     #[inline(always)]
     pub fn clear(&mut self, additional: usize) {
@@ -266,11 +461,48 @@ impl<T> SafeVec<T> {
         }
         if needs_drop::<T>() {
             for i in self.vec.iter() {
-                unsafe { i.0.assume_init_drop() }
+                unsafe { i.value.assume_init_drop() }
             }
         }
         self.vec.clear(additional);
     }
+    /// 弹出末尾元素，要求独占引用，因此不需要原子操作；被弹出的值交还调用者，不析构任何元素
+    #[inline(always)]
+    pub fn pop(&mut self) -> Option<T> {
+        let len = self.len.get_mut();
+        if *len == 0 {
+            return None;
+        }
+        *len -= 1;
+        let index = *len;
+        let element = unsafe { self.vec.get_unchecked_mut(index) };
+        let value = unsafe { element.value.assume_init_read() };
+        // 清空被弹出槽位的ready位，并回退内部分配计数，避免下一次insert时
+        // advance_committed_len把这个已经移出的槽位重新暴露出来
+        element.ready.store(false, Ordering::Relaxed);
+        *self.vec.len.get_mut() -= 1;
+        Some(value)
+    }
+    /// 截断到指定长度，丢弃`len..`区间的元素，要求独占引用，因此不需要原子操作，
+    /// 析构只发生在被丢弃的元素上
+    #[inline(always)]
+    pub fn truncate(&mut self, len: usize) {
+        let cur = *self.len.get_mut();
+        if len >= cur {
+            return;
+        }
+        for i in len..cur {
+            let element = unsafe { self.vec.get_unchecked_mut(i) };
+            if needs_drop::<T>() {
+                unsafe { element.value.assume_init_drop() };
+            }
+            // 清空被丢弃槽位的ready位，并回退内部分配计数，避免下一次insert/extend时
+            // advance_committed_len把这些已经截断的槽位重新暴露出来
+            element.ready.store(false, Ordering::Relaxed);
+        }
+        *self.len.get_mut() = len;
+        *self.vec.len.get_mut() -= cur - len;
+    }
 }
 impl<T> Index<usize> for SafeVec<T> {
     type Output = T;
@@ -289,7 +521,7 @@ impl<T> Drop for SafeVec<T> {
     fn drop(&mut self) {
         if needs_drop::<T>() {
             for i in self.vec.iter() {
-                unsafe { i.0.assume_init_drop() }
+                unsafe { i.value.assume_init_drop() }
             }
         }
     }
@@ -302,16 +534,100 @@ impl<T> Default for SafeVec<T> {
         }
     }
 }
+impl<T: Clone> Clone for SafeVec<T> {
+    fn clone(&self) -> Self {
+        let len = self.len();
+        let vec = Self::with_capacity(len);
+        for i in 0..len {
+            vec.insert(self.get(i).expect("live element").clone());
+        }
+        vec
+    }
+}
+impl<T> FromIterator<T> for SafeVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        let vec = Self::with_capacity(lower);
+        for value in iter {
+            vec.insert(value);
+        }
+        vec
+    }
+}
 impl<T: Debug> Debug for SafeVec<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         f.debug_list().entries(self.iter()).finish()
     }
 }
 
-struct Element<T>(MaybeUninit<T>);
+/// 一块连续保留的下标区间，逐个产出可写入的槽位
+pub struct Block<'a, T> {
+    arr: &'a Arr<T>,
+    cur: usize,
+    end: usize,
+}
+impl<'a, T: Default> Iterator for Block<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cur >= self.end {
+            return None;
+        }
+        let r = self.arr.load_alloc(self.cur);
+        self.cur += 1;
+        Some(r)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.cur;
+        (remaining, Some(remaining))
+    }
+}
+
+/// 每个槽位携带一个独立的ready位，写者提交时只置位自己的槽位，不必等待更低下标的写者
+struct Element<T> {
+    ready: AtomicBool,
+    value: MaybeUninit<T>,
+}
 impl<T> Default for Element<T> {
     fn default() -> Self {
-        Self(MaybeUninit::uninit())
+        Self {
+            ready: AtomicBool::new(false),
+            value: MaybeUninit::uninit(),
+        }
+    }
+}
+
+/// 尝试将`len`向前推进，扫描从当前`len`开始连续的ready槽位，遇到第一个未ready的槽位就停止
+///
+/// 多个写者可能同时调用，每轮用一次CAS推进前沿；CAS失败说明别的写者已经推进过，重新读取`len`
+/// 再扫描。扫描遇到未ready的槽位就返回，把之后的推进留给填补空缺的写者，因此某个写者不会因为
+/// 等待别的下标提交而自旋阻塞。
+///
+/// 这依赖一个前提：任何下标 >= `len`的槽位都不会有`ready == true`。缩短长度的操作（`pop`、
+/// `retain`）放弃槽位时必须把它们的ready位清空，否则这里会把已经被移出/丢弃的槽位当成已提交
+/// 的新数据重新暴露出来。
+fn advance_committed_len<T>(len: &ShareUsize, vec: &AppendVec<Element<T>>) {
+    // 调用方刚置位自己的ready位；SeqCst栅栏保证两个互相等待的写者至少有一个能看到对方的ready位
+    std::sync::atomic::fence(Ordering::SeqCst);
+    loop {
+        let cur = len.load(Ordering::Acquire);
+        let mut new_len = cur;
+        while let Some(e) = vec.get(new_len) {
+            if !e.ready.load(Ordering::Acquire) {
+                break;
+            }
+            new_len += 1;
+        }
+        if new_len == cur {
+            return;
+        }
+        if len
+            .compare_exchange(cur, new_len, Ordering::Release, Ordering::Relaxed)
+            .is_ok()
+        {
+            return;
+        }
     }
 }
 
@@ -320,7 +636,9 @@ impl<'a, T> Iterator for SafeVecIter<'a, T> {
     type Item = &'a mut T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next().map(|r| unsafe { transmute(r.0.as_ptr()) })
+        self.0
+            .next()
+            .map(|r| unsafe { transmute(r.value.as_ptr()) })
     }
     fn size_hint(&self) -> (usize, Option<usize>) {
         self.0.size_hint()
@@ -330,6 +648,7 @@ impl<'a, T> Iterator for SafeVecIter<'a, T> {
 pub struct Entry<'a, T> {
     index: usize,
     len: &'a ShareUsize,
+    vec: &'a AppendVec<Element<T>>,
     value: &'a mut Element<T>,
 }
 impl<'a, T> Entry<'_, T> {
@@ -337,22 +656,87 @@ impl<'a, T> Entry<'_, T> {
         self.index
     }
     pub fn insert(self, value: T) {
-        *self.value = Element(MaybeUninit::new(value));
+        self.value.value = MaybeUninit::new(value);
     }
 }
 impl<'a, T> Drop for Entry<'_, T> {
     fn drop(&mut self) {
-        while self
-            .len
-            .compare_exchange(
-                self.index,
-                self.index + 1,
-                Ordering::Release,
-                Ordering::Relaxed,
-            )
-            .is_err()
-        {
-            std::hint::spin_loop();
+        self.value.ready.store(true, Ordering::Release);
+        advance_committed_len(self.len, self.vec);
+    }
+}
+
+/// serde支持，序列化`0..len()`的有效区间为一个序列，反序列化时通过`insert`逐个重建
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::{AppendVec, SafeVec};
+    use core::fmt;
+    use core::marker::PhantomData;
+    use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+    use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+    impl<T: Default + Serialize> Serialize for AppendVec<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let len = self.len();
+            let mut seq = serializer.serialize_seq(Some(len))?;
+            for i in 0..len {
+                seq.serialize_element(&self[i])?;
+            }
+            seq.end()
+        }
+    }
+
+    struct AppendVecVisitor<T>(PhantomData<T>);
+    impl<'de, T: Default + Deserialize<'de>> Visitor<'de> for AppendVecVisitor<T> {
+        type Value = AppendVec<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a sequence")
+        }
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let vec = AppendVec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(value) = seq.next_element()? {
+                vec.insert(value);
+            }
+            Ok(vec)
+        }
+    }
+    impl<'de, T: Default + Deserialize<'de>> Deserialize<'de> for AppendVec<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_seq(AppendVecVisitor(PhantomData))
+        }
+    }
+
+    impl<T: Serialize> Serialize for SafeVec<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let len = self.len();
+            let mut seq = serializer.serialize_seq(Some(len))?;
+            for i in 0..len {
+                // SafeVec的元素通过Element的as_ptr读取已初始化的值
+                seq.serialize_element(self.get(i).expect("live element"))?;
+            }
+            seq.end()
+        }
+    }
+
+    struct SafeVecVisitor<T>(PhantomData<T>);
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for SafeVecVisitor<T> {
+        type Value = SafeVec<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a sequence")
+        }
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let vec = SafeVec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(value) = seq.next_element()? {
+                vec.insert(value);
+            }
+            Ok(vec)
+        }
+    }
+    impl<'de, T: Deserialize<'de>> Deserialize<'de> for SafeVec<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_seq(SafeVecVisitor(PhantomData))
         }
     }
 }
@@ -360,6 +744,25 @@ impl<'a, T> Drop for Entry<'_, T> {
 #[cfg(test)]
 mod tests {
     use crate::*;
+    use std::cell::Cell;
+
+    thread_local! {
+        static DROP_COUNT: Cell<usize> = Cell::new(0);
+    }
+    /// 每次析构都会记录到线程局部计数器，用于校验retain/pop/truncate不会漏删或重复析构
+    #[derive(Default)]
+    struct DropCounter(i32);
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROP_COUNT.with(|c| c.set(c.get() + 1));
+        }
+    }
+    fn reset_drop_count() {
+        DROP_COUNT.with(|c| c.set(0));
+    }
+    fn drop_count() -> usize {
+        DROP_COUNT.with(|c| c.get())
+    }
 
     #[test]
     fn test() {
@@ -397,4 +800,234 @@ mod tests {
         let hello1 = vec.insert("Hello");
         assert_eq!(vec[hello1], "Hello");
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_append_vec_serde_round_trip() {
+        let vec: AppendVec<i32> = AppendVec::with_capacity(4);
+        vec.insert(1);
+        vec.insert(2);
+        vec.insert(3);
+        let json = serde_json::to_string(&vec).unwrap();
+        let restored: AppendVec<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.to_vec(), vec.to_vec());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_safe_vec_serde_round_trip() {
+        let vec: SafeVec<i32> = SafeVec::with_capacity(4);
+        vec.insert(1);
+        vec.insert(2);
+        vec.insert(3);
+        let json = serde_json::to_string(&vec).unwrap();
+        let restored: SafeVec<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.to_vec(), vec.to_vec());
+    }
+
+    #[test]
+    fn test_append_vec_extend() {
+        let vec: AppendVec<i32> = AppendVec::with_capacity(4);
+        vec.insert(0);
+        vec.extend(vec![1, 2, 3]);
+        assert_eq!(vec.len(), 4);
+        assert_eq!(vec.to_vec(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_safe_vec_extend() {
+        let vec: SafeVec<i32> = SafeVec::with_capacity(4);
+        vec.insert(0);
+        vec.extend(vec![1, 2, 3]);
+        assert_eq!(vec.len(), 4);
+        assert_eq!(vec.to_vec(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_safe_vec_concurrent_extend_out_of_order_commit() {
+        use std::sync::Barrier;
+        use std::thread;
+
+        let vec: SafeVec<usize> = SafeVec::with_capacity(8);
+        let barrier = Barrier::new(2);
+        thread::scope(|s| {
+            let first = vec.alloc_entry(); // reserves index 0, held uncommitted
+            s.spawn(|| {
+                vec.extend(vec![10, 20, 30]); // reserves indices 1..4, commits immediately
+                barrier.wait();
+            });
+            barrier.wait();
+            // the extend's block is ready but index 0 is still held by `first`, so the gap
+            // must keep len() from advancing past it
+            assert_eq!(vec.len(), 0);
+            first.insert(1); // drops, committing index 0 and closing the gap
+        });
+        assert_eq!(vec.len(), 4);
+        assert_eq!(vec.to_vec(), vec![1, 10, 20, 30]);
+    }
+
+    #[test]
+    fn test_safe_vec_concurrent_out_of_order_commit() {
+        use std::sync::Barrier;
+        use std::thread;
+
+        let vec: SafeVec<usize> = SafeVec::with_capacity(4);
+        let barrier = Barrier::new(2);
+        thread::scope(|s| {
+            let first = vec.alloc_entry(); // reserves index 0, held uncommitted
+            s.spawn(|| {
+                let second = vec.alloc_entry(); // reserves index 1
+                second.insert(20); // drops immediately, committing index 1's ready bit
+                barrier.wait();
+            });
+            barrier.wait();
+            // index 1 is ready but index 0 is still held by `first`, so the gap must block len()
+            assert_eq!(vec.len(), 0);
+            first.insert(10); // drops, committing index 0 and closing the gap
+        });
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec.get(0), Some(&10));
+        assert_eq!(vec.get(1), Some(&20));
+    }
+
+    #[test]
+    fn test_safe_vec_retain_then_reinsert_reuses_slots() {
+        let mut vec: SafeVec<i32> = SafeVec::with_capacity(4);
+        vec.insert(1);
+        vec.insert(2);
+        vec.insert(3);
+        vec.insert(4);
+        vec.retain(0, |v| *v % 2 == 0);
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec.to_vec(), vec![2, 4]);
+        let index = vec.insert(5);
+        assert_eq!(
+            index, 2,
+            "retain must roll back the inner alloc counter so freed slots are reused"
+        );
+        assert_eq!(vec.len(), 3);
+        assert_eq!(vec.to_vec(), vec![2, 4, 5]);
+    }
+
+    #[test]
+    fn test_safe_vec_pop_then_insert_reuses_slot() {
+        let mut vec: SafeVec<i32> = SafeVec::with_capacity(4);
+        vec.insert(1);
+        vec.insert(2);
+        assert_eq!(vec.pop(), Some(2));
+        assert_eq!(vec.len(), 1);
+        let index = vec.insert(3);
+        assert_eq!(
+            index, 1,
+            "pop must roll back the inner alloc counter so the freed slot is reused"
+        );
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec.get(1), Some(&3));
+    }
+
+    #[test]
+    fn test_append_vec_retain_drops_discarded_and_keeps_survivors() {
+        let mut vec: AppendVec<DropCounter> = AppendVec::with_capacity(2);
+        for i in 0..5 {
+            vec.insert(DropCounter(i));
+        }
+        // insert覆盖槽位里的默认值时也会析构一次，因此在插入之后才开始计数
+        reset_drop_count();
+        vec.retain(1, |c| c.0 % 2 == 0); // keeps 0, 2, 4; drops 1, 3
+        assert_eq!(vec.len(), 3);
+        assert_eq!(
+            (0..vec.len()).map(|i| vec.get(i).unwrap().0).collect::<Vec<_>>(),
+            vec![0, 2, 4]
+        );
+        assert_eq!(drop_count(), 2, "retain must drop exactly the discarded elements");
+        // `additional` must be honored by the settle at the end of retain
+        assert!(vec.vec_capacity() >= vec.len() + 1);
+    }
+
+    #[test]
+    fn test_append_vec_clone_and_from_iter_and_to_vec() {
+        let vec: AppendVec<i32> = AppendVec::from_iter([1, 2, 3]);
+        assert_eq!(vec.to_vec(), vec![1, 2, 3]);
+        let cloned = vec.clone();
+        assert_eq!(cloned.to_vec(), vec![1, 2, 3]);
+        cloned.insert(4);
+        assert_eq!(cloned.len(), 4);
+        assert_eq!(vec.len(), 3, "cloning must not alias the original's storage");
+    }
+
+    #[test]
+    fn test_safe_vec_clone_and_from_iter_and_to_vec() {
+        let vec: SafeVec<i32> = SafeVec::from_iter([1, 2, 3]);
+        assert_eq!(vec.to_vec(), vec![1, 2, 3]);
+        let cloned = vec.clone();
+        assert_eq!(cloned.to_vec(), vec![1, 2, 3]);
+        cloned.insert(4);
+        assert_eq!(cloned.len(), 4);
+        assert_eq!(vec.len(), 3, "cloning must not alias the original's storage");
+    }
+
+    #[test]
+    fn test_append_vec_pop_then_insert_reuses_slot() {
+        let mut vec: AppendVec<DropCounter> = AppendVec::with_capacity(4);
+        vec.insert(DropCounter(1));
+        vec.insert(DropCounter(2));
+        // insert覆盖槽位里的默认值时也会析构一次，因此在插入之后才开始计数
+        reset_drop_count();
+        let popped = vec.pop().unwrap();
+        assert_eq!(popped.0, 2);
+        assert_eq!(vec.len(), 1);
+        drop(popped);
+        assert_eq!(
+            drop_count(),
+            1,
+            "pop must hand back ownership instead of dropping (or leaking) the popped value"
+        );
+        let index = vec.insert(DropCounter(3));
+        assert_eq!(index, 1, "pop must roll back len so the freed slot is reused");
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec.get(1).unwrap().0, 3);
+    }
+
+    #[test]
+    fn test_append_vec_truncate_drops_discarded_tail() {
+        let mut vec: AppendVec<DropCounter> = AppendVec::with_capacity(4);
+        for i in 0..4 {
+            vec.insert(DropCounter(i));
+        }
+        // insert覆盖槽位里的默认值时也会析构一次，因此在插入之后才开始计数
+        reset_drop_count();
+        vec.truncate(1);
+        assert_eq!(vec.len(), 1);
+        assert_eq!(
+            drop_count(),
+            3,
+            "truncate must drop exactly the truncated tail, not zero and not the survivor"
+        );
+        assert_eq!(vec.get(0).unwrap().0, 0);
+        let index = vec.insert(DropCounter(9));
+        assert_eq!(index, 1, "truncate must roll back len so freed slots are reused");
+    }
+
+    #[test]
+    fn test_safe_vec_truncate_drops_discarded_tail_then_reinserts() {
+        reset_drop_count();
+        let mut vec: SafeVec<DropCounter> = SafeVec::with_capacity(4);
+        for i in 0..4 {
+            vec.insert(DropCounter(i));
+        }
+        vec.truncate(1);
+        assert_eq!(vec.len(), 1);
+        assert_eq!(
+            drop_count(),
+            3,
+            "truncate must drop exactly the truncated tail, not zero and not the survivor"
+        );
+        let index = vec.insert(DropCounter(9));
+        assert_eq!(
+            index, 1,
+            "truncate must roll back the inner alloc counter and ready bits so freed slots are reused"
+        );
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec.get(1).unwrap().0, 9);
+    }
 }